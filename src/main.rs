@@ -1,11 +1,13 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use druid::{
-    AppLauncher, Color, Data, Env, Event, EventCtx, FontDescriptor, FontWeight, Insets, Lens,
-    LensExt, LocalizedString, theme, Widget, WidgetExt, WindowDesc,
+    AppDelegate, AppLauncher, Application, Color, Command, Data, DelegateCtx, Env, Event,
+    EventCtx, FileDialogOptions, FileSpec, FontDescriptor, FontWeight, Handled, Insets, Lens,
+    LensExt, LocalizedString, Target, theme, Widget, WidgetExt, WindowDesc,
 };
 use druid::widget::{
-    Controller, CrossAxisAlignment, Flex, Label, LensWrap, Parse, RadioGroup, Stepper,
+    Button, Controller, CrossAxisAlignment, Flex, Label, LensWrap, Parse, RadioGroup, Stepper,
     TextBox, ViewSwitcher,
 };
 
@@ -17,12 +19,16 @@ fn main() {
     let data = AppData {
         current_view: 0,
         conversion: ConversionType::Encryption,
-        shift: 6.0,
+        active_panel: ActivePanel::Plaintext,
+        alphabet_set: AlphabetSet::Ascii,
+        cipher: Cipher::Caesar { shift: 6.0 },
         plaintext: "".to_string().into(),
         ciphertext: "".to_string().into(),
+        candidates: "".to_string().into(),
     };
 
     AppLauncher::with_window(window)
+        .delegate(Delegate)
         .log_to_console()
         .launch(data)
         .expect("Application failed to launch");
@@ -32,15 +38,217 @@ fn main() {
 enum ConversionType {
     Encryption,
     Decryption,
+    Crack,
+}
+
+#[derive(Clone, Data, PartialEq)]
+enum ActivePanel {
+    Plaintext,
+    Ciphertext,
+}
+
+#[derive(Clone, Data, PartialEq)]
+enum AlphabetSet {
+    Ascii,
+    Latin1,
+    Greek,
+    Cyrillic,
+}
+
+impl AlphabetSet {
+    fn alphabets(&self) -> Vec<Alphabet> {
+        match self {
+            AlphabetSet::Ascii => vec![
+                Alphabet::new(('A'..='Z').collect()),
+                Alphabet::new(('a'..='z').collect()),
+            ],
+            AlphabetSet::Latin1 => vec![
+                Alphabet::new("ÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖØÙÚÛÜÝÞ".chars().collect()),
+                Alphabet::new("àáâãäåæçèéêëìíîïðñòóôõöøùúûüýþÿ".chars().collect()),
+            ],
+            AlphabetSet::Greek => vec![
+                Alphabet::new("ΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩ".chars().collect()),
+                Alphabet::new("αβγδεζηθικλμνξοπρστυφχψω".chars().collect()),
+            ],
+            AlphabetSet::Cyrillic => vec![
+                Alphabet::new(('А'..='Я').collect()),
+                Alphabet::new(('а'..='я').collect()),
+            ],
+        }
+    }
+
+    /// The length of the set's longest alphabet, used to bound the shift `Stepper`.
+    fn max_len(&self) -> usize {
+        self.alphabets().iter().map(Alphabet::len).max().unwrap_or(26)
+    }
+}
+
+/// An ordered set of letters a shift is performed within, plus the reverse lookup
+/// needed to find a letter's position without a linear scan.
+struct Alphabet {
+    letters: Vec<char>,
+    index: HashMap<char, usize>,
+}
+
+impl Alphabet {
+    fn new(letters: Vec<char>) -> Self {
+        let index = letters.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+        Alphabet { letters, index }
+    }
+
+    fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    fn position(&self, c: char) -> Option<usize> {
+        self.index.get(&c).copied()
+    }
+
+    fn shift_char(&self, c: char, shift: i16) -> Option<char> {
+        self.position(c).map(|i| {
+            let shifted = (i as i16 + shift).rem_euclid(self.letters.len() as i16);
+            self.letters[shifted as usize]
+        })
+    }
+
+    /// Atbash reflects a letter's position about the middle of the alphabet.
+    fn reflect_char(&self, c: char) -> Option<char> {
+        self.position(c)
+            .map(|i| self.letters[self.letters.len() - 1 - i])
+    }
+}
+
+#[derive(Clone, Data, PartialEq)]
+enum Cipher {
+    Caesar { shift: f64 },
+    Vigenere { key: Arc<String> },
+    Atbash,
+}
+
+#[derive(Clone, Data, PartialEq)]
+enum CipherKind {
+    Caesar,
+    Vigenere,
+    Atbash,
+}
+
+impl Cipher {
+    fn kind(&self) -> CipherKind {
+        match self {
+            Cipher::Caesar { .. } => CipherKind::Caesar,
+            Cipher::Vigenere { .. } => CipherKind::Vigenere,
+            Cipher::Atbash => CipherKind::Atbash,
+        }
+    }
+}
+
+/// Lenses onto a `Cipher`'s variant-specific fields, so widgets for one variant's
+/// parameters can bind directly to `AppData::cipher` without matching on it by hand.
+#[derive(Clone, Copy)]
+struct CipherKindLens;
+
+impl Lens<Cipher, CipherKind> for CipherKindLens {
+    fn with<V, F: FnOnce(&CipherKind) -> V>(&self, data: &Cipher, f: F) -> V {
+        f(&data.kind())
+    }
+
+    fn with_mut<V, F: FnOnce(&mut CipherKind) -> V>(&self, data: &mut Cipher, f: F) -> V {
+        let mut kind = data.kind();
+        let v = f(&mut kind);
+        if kind != data.kind() {
+            *data = match kind {
+                CipherKind::Caesar => Cipher::Caesar { shift: 1.0 },
+                CipherKind::Vigenere => Cipher::Vigenere { key: Arc::from("".to_string()) },
+                CipherKind::Atbash => Cipher::Atbash,
+            };
+        }
+        v
+    }
+}
+
+#[derive(Clone, Copy)]
+struct CaesarShiftLens;
+
+impl Lens<Cipher, f64> for CaesarShiftLens {
+    fn with<V, F: FnOnce(&f64) -> V>(&self, data: &Cipher, f: F) -> V {
+        match data {
+            Cipher::Caesar { shift } => f(shift),
+            _ => f(&1.0),
+        }
+    }
+
+    fn with_mut<V, F: FnOnce(&mut f64) -> V>(&self, data: &mut Cipher, f: F) -> V {
+        match data {
+            Cipher::Caesar { shift } => f(shift),
+            _ => f(&mut 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct VigenereKeyLens;
+
+impl Lens<Cipher, Arc<String>> for VigenereKeyLens {
+    fn with<V, F: FnOnce(&Arc<String>) -> V>(&self, data: &Cipher, f: F) -> V {
+        match data {
+            Cipher::Vigenere { key } => f(key),
+            _ => f(&Arc::from("".to_string())),
+        }
+    }
+
+    fn with_mut<V, F: FnOnce(&mut Arc<String>) -> V>(&self, data: &mut Cipher, f: F) -> V {
+        match data {
+            Cipher::Vigenere { key } => f(key),
+            _ => f(&mut Arc::from("".to_string())),
+        }
+    }
 }
 
 #[derive(Clone, Data, Lens)]
 struct AppData {
     current_view: u32,
     conversion: ConversionType,
-    shift: f64,
+    active_panel: ActivePanel,
+    alphabet_set: AlphabetSet,
+    cipher: Cipher,
     plaintext: Arc<String>,
     ciphertext: Arc<String>,
+    candidates: Arc<String>,
+}
+
+struct Delegate;
+
+impl AppDelegate<AppData> for Delegate {
+    fn command(
+        &mut self,
+        _ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut AppData,
+        _env: &Env,
+    ) -> Handled {
+        if let Some(file_info) = cmd.get(druid::commands::OPEN_FILE) {
+            match std::fs::read_to_string(file_info.path()) {
+                Ok(contents) => {
+                    data.plaintext = Arc::from(contents);
+                    data.ciphertext = Arc::from(encrypt(&data.plaintext, &data.cipher, &data.alphabet_set));
+                }
+                Err(e) => println!("Error opening file: {}", e),
+            }
+            return Handled::Yes;
+        }
+        if let Some(file_info) = cmd.get(druid::commands::SAVE_FILE_AS) {
+            let text = match data.active_panel {
+                ActivePanel::Plaintext => data.plaintext.as_str(),
+                ActivePanel::Ciphertext => data.ciphertext.as_str(),
+            };
+            if let Err(e) = std::fs::write(file_info.path(), text) {
+                println!("Error saving file: {}", e);
+            }
+            return Handled::Yes;
+        }
+        Handled::No
+    }
 }
 
 struct ConversionController;
@@ -50,26 +258,42 @@ impl<W: Widget<AppData>> Controller<AppData, W> for ConversionController {
         let old_data = data.conversion.to_owned();
         child.event(ctx, event, data, env);
         if !data.conversion.same(&old_data) {
+            data.current_view = match data.conversion {
+                ConversionType::Encryption => 0,
+                ConversionType::Decryption => 1,
+                ConversionType::Crack => 2,
+            };
+        }
+    }
+}
+
+struct CipherController;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for CipherController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppData, env: &Env) {
+        let old_data = data.cipher.to_owned();
+        child.event(ctx, event, data, env);
+        if !data.cipher.same(&old_data) {
             if data.conversion == ConversionType::Encryption {
-                data.current_view = 0;
-            } else {
-                data.current_view = 1;
+                data.ciphertext = Arc::from(encrypt(&data.plaintext.to_owned(), &data.cipher, &data.alphabet_set));
+            } else if data.conversion == ConversionType::Decryption {
+                data.plaintext = Arc::from(decrypt(&data.ciphertext.to_owned(), &data.cipher, &data.alphabet_set));
             }
         }
     }
 }
 
-struct ShiftController;
+struct AlphabetController;
 
-impl<W: Widget<AppData>> Controller<AppData, W> for ShiftController {
+impl<W: Widget<AppData>> Controller<AppData, W> for AlphabetController {
     fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppData, env: &Env) {
-        let old_data = data.shift.to_owned();
+        let old_data = data.alphabet_set.to_owned();
         child.event(ctx, event, data, env);
-        if !data.shift.same(&old_data) {
+        if !data.alphabet_set.same(&old_data) {
             if data.conversion == ConversionType::Encryption {
-                data.ciphertext = Arc::from(encrypt(&data.plaintext.to_owned(), data.shift as i16));
-            } else {
-                data.plaintext = Arc::from(encrypt(&data.ciphertext.to_owned(), -data.shift as i16));
+                data.ciphertext = Arc::from(encrypt(&data.plaintext.to_owned(), &data.cipher, &data.alphabet_set));
+            } else if data.conversion == ConversionType::Decryption {
+                data.plaintext = Arc::from(decrypt(&data.ciphertext.to_owned(), &data.cipher, &data.alphabet_set));
             }
         }
     }
@@ -81,8 +305,11 @@ impl<W: Widget<AppData>> Controller<AppData, W> for PlaintextController {
     fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppData, env: &Env) {
         let old_data = data.plaintext.to_owned();
         child.event(ctx, event, data, env);
+        if ctx.has_focus() {
+            data.active_panel = ActivePanel::Plaintext;
+        }
         if data.conversion == ConversionType::Encryption && data.plaintext.to_owned().len() != data.ciphertext.to_owned().len() {
-            data.ciphertext = Arc::from(encrypt(&old_data, data.shift as i16));
+            data.ciphertext = Arc::from(encrypt(&old_data, &data.cipher, &data.alphabet_set));
         } else {
             data.plaintext = old_data;
         }
@@ -95,39 +322,113 @@ impl<W: Widget<AppData>> Controller<AppData, W> for CiphertextController {
     fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppData, env: &Env) {
         let old_data = data.ciphertext.to_owned();
         child.event(ctx, event, data, env);
+        if ctx.has_focus() {
+            data.active_panel = ActivePanel::Ciphertext;
+        }
         if data.conversion == ConversionType::Decryption && data.ciphertext.to_owned().len() != data.plaintext.to_owned().len() {
-            data.plaintext = Arc::from(encrypt(&old_data, -data.shift as i16));
+            data.plaintext = Arc::from(decrypt(&old_data, &data.cipher, &data.alphabet_set));
+        } else if data.conversion == ConversionType::Crack && data.ciphertext.to_owned().len() != data.plaintext.to_owned().len() {
+            crack_ciphertext(data, &old_data);
         } else {
             data.ciphertext = old_data;
         }
     }
 }
 
-fn build_root() -> impl Widget<AppData> {
-    let conversion_picker = RadioGroup::new(vec![
-        ("Encryption", ConversionType::Encryption),
-        ("Decryption", ConversionType::Decryption),
-    ])
-        .lens(AppData::conversion)
-        .controller(ConversionController);
+fn build_toolbar() -> impl Widget<AppData> {
+    let txt_file = FileSpec::new("Text", &["txt"]);
+    let open_options = FileDialogOptions::new().allowed_types(vec![txt_file]);
+    let save_options = FileDialogOptions::new()
+        .allowed_types(vec![txt_file])
+        .default_type(txt_file);
+
+    let open_button = Button::new("Open").on_click(move |ctx, _data: &mut AppData, _env| {
+        ctx.submit_command(druid::commands::SHOW_OPEN_PANEL.with(open_options.clone()));
+    });
+    let save_button = Button::new("Save").on_click(move |ctx, _data: &mut AppData, _env| {
+        ctx.submit_command(druid::commands::SHOW_SAVE_PANEL.with(save_options.clone()));
+    });
+
+    Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(open_button)
+        .with_default_spacer()
+        .with_child(save_button)
+        .padding(Insets::new(20.0, 10.0, 20.0, 0.0))
+}
 
+fn build_shift_row(max_len: usize) -> impl Widget<AppData> {
     let shift_input = LensWrap::new(
         Parse::new(TextBox::new()),
-        AppData::shift.map(|x| Some(*x), |x, y| *x = y.unwrap_or(5.0)),
+        AppData::cipher.then(CaesarShiftLens).map(|x| Some(*x), |x, y| *x = y.unwrap_or(5.0)),
     );
     let shift_stepper = Stepper::new()
-        .with_range(1.0, 25.0)
+        .with_range(1.0, (max_len - 1) as f64)
         .with_step(1.0)
         .with_wraparound(false)
         .border(theme::DISABLED_BUTTON_DARK, 2.0)
-        .lens(AppData::shift)
-        .controller(ShiftController);
-    let shift_row = Flex::row()
+        .lens(AppData::cipher.then(CaesarShiftLens))
+        .controller(CipherController);
+
+    Flex::row()
         .cross_axis_alignment(CrossAxisAlignment::Start)
         .with_child(shift_input)
         .with_child(shift_stepper)
         .padding(Insets::new(20.0, 0.0, 20.0, 0.0))
-        .expand_height();
+        .expand_height()
+}
+
+fn build_key_row() -> impl Widget<AppData> {
+    Flex::row()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            TextBox::new()
+                .lens(AppData::cipher.then(VigenereKeyLens))
+                .controller(CipherController)
+        )
+        .padding(Insets::new(20.0, 0.0, 20.0, 0.0))
+        .expand_height()
+}
+
+fn build_root() -> impl Widget<AppData> {
+    let conversion_picker = RadioGroup::new(vec![
+        ("Encryption", ConversionType::Encryption),
+        ("Decryption", ConversionType::Decryption),
+        ("Crack", ConversionType::Crack),
+    ])
+        .lens(AppData::conversion)
+        .controller(ConversionController);
+
+    let alphabet_picker = RadioGroup::new(vec![
+        ("ASCII", AlphabetSet::Ascii),
+        ("Latin-1", AlphabetSet::Latin1),
+        ("Greek", AlphabetSet::Greek),
+        ("Cyrillic", AlphabetSet::Cyrillic),
+    ])
+        .lens(AppData::alphabet_set)
+        .controller(AlphabetController);
+
+    let cipher_kind_picker = RadioGroup::new(vec![
+        ("Caesar", CipherKind::Caesar),
+        ("Vigenère", CipherKind::Vigenere),
+        ("Atbash", CipherKind::Atbash),
+    ])
+        .lens(AppData::cipher.then(CipherKindLens))
+        .controller(CipherController);
+
+    let cipher_param_row = ViewSwitcher::new(
+        |data: &AppData, _env| (data.cipher.kind(), data.alphabet_set.max_len()),
+        |(kind, max_len), _data, _env| -> Box<dyn Widget<AppData>> {
+            match kind {
+                CipherKind::Caesar => Box::new(build_shift_row(*max_len)),
+                CipherKind::Vigenere => Box::new(build_key_row()),
+                CipherKind::Atbash => Box::new(
+                    Label::new("Atbash has no parameters")
+                        .padding(Insets::new(20.0, 0.0, 20.0, 0.0))
+                ),
+            }
+        },
+    );
 
     let view_switcher = ViewSwitcher::new(
         |data: &AppData, _env| data.current_view,
@@ -140,7 +441,7 @@ fn build_root() -> impl Widget<AppData> {
                     )
                 )
             }
-            _ => {
+            1 => {
                 Box::new(
                     build_textbox_view(
                         build_ciphertext_input("Input"),
@@ -148,6 +449,9 @@ fn build_root() -> impl Widget<AppData> {
                     )
                 )
             }
+            _ => {
+                Box::new(build_crack_view())
+            }
         },
     );
 
@@ -172,6 +476,8 @@ fn build_root() -> impl Widget<AppData> {
                         )
                 )
                 .with_default_spacer()
+                .with_child(build_toolbar())
+                .with_default_spacer()
                 .with_child(
                     Flex::column()
                         .cross_axis_alignment(CrossAxisAlignment::Start)
@@ -186,12 +492,32 @@ fn build_root() -> impl Widget<AppData> {
                 )
                 .with_default_spacer()
                 .with_child(
-                    Label::new("Shift:")
-                        .expand_width()
+                    Flex::column()
+                        .cross_axis_alignment(CrossAxisAlignment::Start)
+                        .with_child(
+                            Label::new("Alphabet:")
+                                .expand_width()
+                        )
+                        .with_default_spacer()
+                        .with_child(alphabet_picker)
+                        .with_default_spacer()
+                        .padding(Insets::new(20.0, 0.0, 20.0, 0.0))
+                )
+                .with_default_spacer()
+                .with_child(
+                    Flex::column()
+                        .cross_axis_alignment(CrossAxisAlignment::Start)
+                        .with_child(
+                            Label::new("Cipher:")
+                                .expand_width()
+                        )
+                        .with_default_spacer()
+                        .with_child(cipher_kind_picker)
+                        .with_default_spacer()
                         .padding(Insets::new(20.0, 0.0, 20.0, 0.0))
                 )
                 .with_default_spacer()
-                .with_flex_child(shift_row, 1.0)
+                .with_flex_child(cipher_param_row, 1.0)
                 .fix_width(220.0)
                 .background(theme::DISABLED_BUTTON_DARK)
                 .border(theme::BORDER_DARK, 1.0)
@@ -201,6 +527,18 @@ fn build_root() -> impl Widget<AppData> {
 }
 
 fn build_plaintext_input(secondary_label: &str) -> impl Widget<AppData> {
+    let copy_button = Button::new("Copy").on_click(|_ctx, data: &mut AppData, _env| {
+        Application::global().clipboard().put_string(data.plaintext.as_str());
+    });
+    let paste_button = Button::new("Paste").on_click(|_ctx, data: &mut AppData, _env| {
+        if let Some(text) = Application::global().clipboard().get_string() {
+            data.plaintext = Arc::from(text);
+            if data.conversion == ConversionType::Encryption {
+                data.ciphertext = Arc::from(encrypt(&data.plaintext, &data.cipher, &data.alphabet_set));
+            }
+        }
+    });
+
     Flex::column()
         .with_child(
             Flex::row()
@@ -212,6 +550,9 @@ fn build_plaintext_input(secondary_label: &str) -> impl Widget<AppData> {
                     build_secondary_label(secondary_label),
                     1.0,
                 )
+                .with_child(copy_button)
+                .with_default_spacer()
+                .with_child(paste_button)
         )
         .with_default_spacer()
         .with_child(
@@ -223,6 +564,21 @@ fn build_plaintext_input(secondary_label: &str) -> impl Widget<AppData> {
 }
 
 fn build_ciphertext_input(secondary_label: &str) -> impl Widget<AppData> {
+    let copy_button = Button::new("Copy").on_click(|_ctx, data: &mut AppData, _env| {
+        Application::global().clipboard().put_string(data.ciphertext.as_str());
+    });
+    let paste_button = Button::new("Paste").on_click(|_ctx, data: &mut AppData, _env| {
+        if let Some(text) = Application::global().clipboard().get_string() {
+            data.ciphertext = Arc::from(text);
+            if data.conversion == ConversionType::Decryption {
+                data.plaintext = Arc::from(decrypt(&data.ciphertext, &data.cipher, &data.alphabet_set));
+            } else if data.conversion == ConversionType::Crack {
+                let ciphertext = data.ciphertext.to_owned();
+                crack_ciphertext(data, &ciphertext);
+            }
+        }
+    });
+
     Flex::column()
         .with_child(
             Flex::row()
@@ -234,6 +590,9 @@ fn build_ciphertext_input(secondary_label: &str) -> impl Widget<AppData> {
                     build_secondary_label(secondary_label),
                     1.0,
                 )
+                .with_child(copy_button)
+                .with_default_spacer()
+                .with_child(paste_button)
         )
         .with_default_spacer()
         .with_child(
@@ -256,6 +615,25 @@ fn build_textbox_view<W: 'static + Widget<AppData>, S: 'static + Widget<AppData>
         .background(theme::DISABLED_BUTTON_DARK)
 }
 
+fn build_crack_view() -> impl Widget<AppData> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_default_spacer()
+        .with_child(build_ciphertext_input("Input"))
+        .with_default_spacer()
+        .with_child(build_plaintext_input("Output"))
+        .with_default_spacer()
+        .with_child(Label::new("Top candidate shifts:").expand_width())
+        .with_default_spacer()
+        .with_child(
+            Label::new(|data: &AppData, _env: &Env| data.candidates.to_string())
+                .expand_width()
+        )
+        .padding(Insets::new(20.0, 10.0, 10.0, 10.0))
+        .fix_width(500.0)
+        .background(theme::DISABLED_BUTTON_DARK)
+}
+
 fn build_primary_label(label: &str, color: Color) -> impl Widget<AppData> {
     Flex::column()
         .with_child(
@@ -285,18 +663,179 @@ fn build_secondary_label(label: &str) -> impl Widget<AppData> {
         .expand_width()
 }
 
-fn encrypt(plaintext: &str, shift: i16) -> String {
-    let mut ciphertext = String::with_capacity(plaintext.len());
-    for c in plaintext.chars() {
-        if c.is_alphabetic() {
-            if c.is_uppercase() {
-                ciphertext.push((65 + ((c as u8) as i16 + shift - 65).rem_euclid(26)) as u8 as char);
-            } else {
-                ciphertext.push((97 + ((c as u8) as i16 + shift - 97).rem_euclid(26)) as u8 as char);
+fn apply_cipher(text: &str, cipher: &Cipher, alphabet_set: &AlphabetSet, reverse: bool) -> String {
+    let sign: i16 = if reverse { -1 } else { 1 };
+    let alphabets = alphabet_set.alphabets();
+    let key_shifts: Vec<i16> = match cipher {
+        Cipher::Vigenere { key } => key
+            .chars()
+            .filter_map(|key_char| alphabets.iter().find_map(|alphabet| alphabet.position(key_char)))
+            .map(|position| position as i16)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut key_pos = 0usize;
+
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        let alphabet = match alphabets.iter().find(|alphabet| alphabet.position(c).is_some()) {
+            Some(alphabet) => alphabet,
+            None => {
+                result.push(c);
+                continue;
             }
-        } else {
-            ciphertext.push(c);
+        };
+
+        let shifted = match cipher {
+            Cipher::Caesar { shift } => alphabet.shift_char(c, sign * (*shift as i16)),
+            Cipher::Atbash => alphabet.reflect_char(c),
+            Cipher::Vigenere { .. } => {
+                if key_shifts.is_empty() {
+                    Some(c)
+                } else {
+                    let key_shift = key_shifts[key_pos % key_shifts.len()];
+                    key_pos += 1;
+                    alphabet.shift_char(c, sign * key_shift)
+                }
+            }
+        };
+        result.push(shifted.unwrap_or(c));
+    }
+    result
+}
+
+fn encrypt(plaintext: &str, cipher: &Cipher, alphabet_set: &AlphabetSet) -> String {
+    apply_cipher(plaintext, cipher, alphabet_set, false)
+}
+
+fn decrypt(ciphertext: &str, cipher: &Cipher, alphabet_set: &AlphabetSet) -> String {
+    apply_cipher(ciphertext, cipher, alphabet_set, true)
+}
+
+const ENGLISH_LETTER_FREQUENCIES: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966, 0.00153,
+    0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056,
+    0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// English letter-frequency analysis only makes sense against the Latin alphabet,
+/// so cracking always decrypts against ASCII regardless of the selected alphabet.
+fn rank_shifts(ciphertext: &str) -> Vec<(i16, f64)> {
+    let mut ranked: Vec<(i16, f64)> = (0..26)
+        .map(|shift| {
+            let candidate = decrypt(ciphertext, &Cipher::Caesar { shift: shift as f64 }, &AlphabetSet::Ascii);
+            let mut counts = [0u32; 26];
+            let mut total = 0u32;
+            for c in candidate.chars() {
+                if c.is_ascii_alphabetic() {
+                    counts[(c.to_ascii_lowercase() as u8 - b'a') as usize] += 1;
+                    total += 1;
+                }
+            }
+            if total == 0 {
+                return (shift, f64::MAX);
+            }
+            let chi_squared: f64 = counts
+                .iter()
+                .zip(ENGLISH_LETTER_FREQUENCIES.iter())
+                .map(|(&observed, &expected_freq)| {
+                    let expected = expected_freq * total as f64;
+                    (observed as f64 - expected).powi(2) / expected
+                })
+                .sum();
+            (shift, chi_squared)
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    ranked
+}
+
+/// Recovers the shift for `ciphertext`, updates `AppData::cipher`/`plaintext`,
+/// and records the top-ranked candidates. Shared by `CiphertextController` and
+/// the ciphertext panel's paste button so both paths stay in sync.
+fn crack_ciphertext(data: &mut AppData, ciphertext: &str) {
+    let ranked = rank_shifts(ciphertext);
+    let shift = match ranked.first() {
+        Some((shift, score)) if *score != f64::MAX => *shift,
+        _ => 0,
+    };
+    data.cipher = Cipher::Caesar { shift: shift as f64 };
+    data.plaintext = Arc::from(decrypt(ciphertext, &data.cipher, &AlphabetSet::Ascii));
+    data.candidates = Arc::from(
+        ranked
+            .iter()
+            .take(3)
+            .map(|(shift, score)| format!("shift {}: {:.2}", shift, score))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caesar_round_trips_across_alphabet_sets() {
+        for alphabet_set in [AlphabetSet::Ascii, AlphabetSet::Latin1, AlphabetSet::Greek, AlphabetSet::Cyrillic] {
+            let plaintext = alphabet_set.alphabets()[0].letters.iter().collect::<String>();
+            let cipher = Cipher::Caesar { shift: 7.0 };
+            let ciphertext = encrypt(&plaintext, &cipher, &alphabet_set);
+            assert_eq!(decrypt(&ciphertext, &cipher, &alphabet_set), plaintext);
         }
     }
-    ciphertext
+
+    #[test]
+    fn atbash_round_trips() {
+        let plaintext = "Hello, World!".to_string();
+        let cipher = Cipher::Atbash;
+        let ciphertext = encrypt(&plaintext, &cipher, &AlphabetSet::Ascii);
+        assert_eq!(decrypt(&ciphertext, &cipher, &AlphabetSet::Ascii), plaintext);
+    }
+
+    #[test]
+    fn vigenere_round_trips_and_preserves_punctuation() {
+        let plaintext = "Attack at dawn!".to_string();
+        let cipher = Cipher::Vigenere { key: Arc::from("lemon".to_string()) };
+        let ciphertext = encrypt(&plaintext, &cipher, &AlphabetSet::Ascii);
+        assert_eq!(ciphertext, "Lxfopv ef rnhr!");
+        assert_eq!(decrypt(&ciphertext, &cipher, &AlphabetSet::Ascii), plaintext);
+    }
+
+    #[test]
+    fn vigenere_skips_key_chars_outside_the_active_alphabet_instead_of_using_shift_zero() {
+        // '1' isn't in any `AlphabetSet::Ascii` alphabet, so it should be dropped from the
+        // effective key entirely rather than contributing a shift-0 slot; if it instead fell
+        // back to shift 0, "b1b" would cycle as [1, 0, 1] (mod 3) instead of [1, 1] (mod 2).
+        let plaintext = "abcdefgh".to_string();
+        let key_with_invalid_char = Cipher::Vigenere { key: Arc::from("b1b".to_string()) };
+        let key_with_invalid_char_dropped = Cipher::Vigenere { key: Arc::from("bb".to_string()) };
+        let with_invalid = encrypt(&plaintext, &key_with_invalid_char, &AlphabetSet::Ascii);
+        let with_dropped = encrypt(&plaintext, &key_with_invalid_char_dropped, &AlphabetSet::Ascii);
+        assert_eq!(with_invalid, with_dropped);
+    }
+
+    #[test]
+    fn chars_outside_every_alphabet_pass_through_unchanged() {
+        let plaintext = "a1 b2!".to_string();
+        let cipher = Cipher::Caesar { shift: 3.0 };
+        let ciphertext = encrypt(&plaintext, &cipher, &AlphabetSet::Ascii);
+        assert_eq!(ciphertext, "d1 e2!");
+    }
+
+    #[test]
+    fn rank_shifts_recovers_a_known_shift_from_an_english_sample() {
+        let plaintext = "the quick brown fox jumps over the lazy dog";
+        let shift = 5;
+        let ciphertext = encrypt(plaintext, &Cipher::Caesar { shift: shift as f64 }, &AlphabetSet::Ascii);
+        let ranked = rank_shifts(&ciphertext);
+        assert_eq!(ranked.first().map(|(shift, _)| *shift), Some(shift));
+    }
+
+    #[test]
+    fn rank_shifts_on_empty_input_has_no_usable_candidate() {
+        let ranked = rank_shifts("");
+        assert!(ranked.iter().all(|(_, score)| *score == f64::MAX));
+    }
 }
+